@@ -22,3 +22,24 @@ pub fn generate_circle_indices(segments: usize) -> Vec<u32> {
   indices.push(0);
   indices
 }
+
+// Same rim vertices as `generate_circle_vertices`, plus a center vertex at
+// index `segments` so the rim can be triangulated into a fan.
+pub fn generate_filled_circle_vertices(radius: f32, segments: usize) -> Vec<f32> {
+  let mut vertices = generate_circle_vertices(radius, segments);
+  vertices.push(0.0);
+  vertices.push(0.0);
+  vertices.push(0.0); // center, z
+  vertices
+}
+
+pub fn generate_filled_circle_indices(segments: usize) -> Vec<u32> {
+  let center = segments as u32;
+  let mut indices = Vec::with_capacity(segments * 3);
+  for i in 0..segments as u32 {
+    indices.push(center);
+    indices.push(i);
+    indices.push((i + 1) % segments as u32);
+  }
+  indices
+}
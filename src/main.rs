@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use wgpu::util::DeviceExt;
 use winit::{
   event::*,
@@ -9,9 +11,47 @@ mod geometry;
 mod shape;
 mod uniforms;
 
-use geometry::{generate_circle_indices, generate_circle_vertices};
-use shape::{Shape, ShapeType};
-use uniforms::Uniforms;
+use geometry::{
+  generate_circle_indices, generate_circle_vertices, generate_filled_circle_indices,
+  generate_filled_circle_vertices,
+};
+use shape::{FillMode, GradientKind, GradientStop, InstanceData, Shape, ShapeKind};
+use uniforms::Camera2D;
+
+// Groups a shape type's instances into a single buffer for one instanced draw call.
+// `None` for an empty bucket instead of allocating a zero-length buffer nothing will draw from.
+fn build_instance_buffer(device: &wgpu::Device, label: &str, instances: &[InstanceData]) -> Option<wgpu::Buffer> {
+  if instances.is_empty() {
+    return None;
+  }
+  Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some(label),
+    contents: bytemuck::cast_slice(instances),
+    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+  }))
+}
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Built fresh at startup and again whenever the surface resizes, since a
+// depth texture must always match the surface's dimensions.
+fn create_depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
+  let texture = device.create_texture(&wgpu::TextureDescriptor {
+    label: Some("Depth Texture"),
+    size: wgpu::Extent3d {
+      width: config.width,
+      height: config.height,
+      depth_or_array_layers: 1,
+    },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: wgpu::TextureDimension::D2,
+    format: DEPTH_FORMAT,
+    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+    view_formats: &[],
+  });
+  texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
 
 #[tokio::main]
 async fn main() {
@@ -66,33 +106,17 @@ async fn main() {
   };
   surface.configure(&device, &surface_config);
 
-  // -------------------------------------
-  // Bind Group Layout for instances
-  // -------------------------------------
-  let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-    label: Some("Instance Bind Group Layout"),
-    entries: &[wgpu::BindGroupLayoutEntry {
-      binding: 0,
-      visibility: wgpu::ShaderStages::VERTEX,
-      ty: wgpu::BindingType::Buffer {
-        ty: wgpu::BufferBindingType::Uniform,
-        has_dynamic_offset: false,
-        min_binding_size: None,
-      },
-      count: None,
-    }],
-  });
+  let mut depth_view = create_depth_view(&device, &surface_config);
 
   // -------------------------------------
-  // Uniform Buffer and Bind Group
+  // Camera, Uniform Buffer and Bind Group
   // -------------------------------------
-  let mut uniforms = Uniforms {
-    aspect_ratio: size.width as f32 / size.height as f32,
-  };
+  let mut camera = Camera2D::new(size.width as f32 / size.height as f32);
+  let mut camera_dirty = true;
 
   let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
     label: Some("Uniform Buffer"),
-    contents: bytemuck::bytes_of(&uniforms),
+    contents: bytemuck::bytes_of(&camera.to_uniforms()),
     usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
   });
 
@@ -138,6 +162,21 @@ async fn main() {
   });
   let circle_index_count = circle_index_data.len() as u32;
 
+  // Solid fill: rim + center vertex, triangle-fanned instead of line-stripped.
+  let filled_circle_vertex_data = generate_filled_circle_vertices(0.5, circle_segments);
+  let filled_circle_index_data = generate_filled_circle_indices(circle_segments);
+  let filled_circle_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("Filled Circle VB"),
+    contents: bytemuck::cast_slice(&filled_circle_vertex_data),
+    usage: wgpu::BufferUsages::VERTEX,
+  });
+  let filled_circle_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("Filled Circle IB"),
+    contents: bytemuck::cast_slice(&filled_circle_index_data),
+    usage: wgpu::BufferUsages::INDEX,
+  });
+  let filled_circle_index_count = filled_circle_index_data.len() as u32;
+
   // Triangle
   let triangle_vertex_data: &[f32] = &[
     0.0, 0.5, 0.0, // x, y, z
@@ -149,6 +188,27 @@ async fn main() {
     usage: wgpu::BufferUsages::VERTEX,
   });
 
+  // Sprite quad: a unit square carrying UVs, shared by every sprite shape.
+  let sprite_vertex_data: &[f32] = &[
+    // position            // tex_coords
+    -0.5, -0.5, 0.0, 0.0, 1.0, //
+    0.5, -0.5, 0.0, 1.0, 1.0, //
+    0.5, 0.5, 0.0, 1.0, 0.0, //
+    -0.5, 0.5, 0.0, 0.0, 0.0,
+  ];
+  let sprite_index_data: &[u16] = &[0, 1, 2, 2, 3, 0];
+  let sprite_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("Sprite VB"),
+    contents: bytemuck::cast_slice(sprite_vertex_data),
+    usage: wgpu::BufferUsages::VERTEX,
+  });
+  let sprite_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("Sprite IB"),
+    contents: bytemuck::cast_slice(sprite_index_data),
+    usage: wgpu::BufferUsages::INDEX,
+  });
+  let sprite_index_count = sprite_index_data.len() as u32;
+
   // -------------------------------------
   // Create Pipelines
   // -------------------------------------
@@ -157,10 +217,69 @@ async fn main() {
     source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
   });
 
+  let vertex_buffer_layout = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Vertex,
+    attributes: &[wgpu::VertexAttribute {
+      offset: 0,
+      shader_location: 0,
+      format: wgpu::VertexFormat::Float32x3,
+    }],
+  };
+
+  // One instance buffer entry per row of the model matrix, stepped per-instance
+  // instead of read from a per-shape uniform bind group.
+  let instance_buffer_layout = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Instance,
+    attributes: &[
+      wgpu::VertexAttribute {
+        offset: 0,
+        shader_location: 1,
+        format: wgpu::VertexFormat::Float32x4,
+      },
+      wgpu::VertexAttribute {
+        offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+        shader_location: 2,
+        format: wgpu::VertexFormat::Float32x4,
+      },
+      wgpu::VertexAttribute {
+        offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+        shader_location: 3,
+        format: wgpu::VertexFormat::Float32x4,
+      },
+      wgpu::VertexAttribute {
+        offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+        shader_location: 4,
+        format: wgpu::VertexFormat::Float32x4,
+      },
+      wgpu::VertexAttribute {
+        offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+        shader_location: 5,
+        format: wgpu::VertexFormat::Float32x4,
+      },
+    ],
+  };
+
+  // Premultiplied-alpha over blending so translucent shape tints compose
+  // correctly instead of fighting with what's already in the framebuffer.
+  let premultiplied_alpha_blend = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+      src_factor: wgpu::BlendFactor::One,
+      dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+      operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+      src_factor: wgpu::BlendFactor::One,
+      dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+      operation: wgpu::BlendOperation::Add,
+    },
+  };
+
   // Circle pipeline
   let circle_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
     label: Some("Circle Pipeline Layout"),
-    bind_group_layouts: &[&uniform_bind_group_layout, &bind_group_layout],
+    bind_group_layouts: &[&uniform_bind_group_layout],
     push_constant_ranges: &[],
   });
 
@@ -170,22 +289,14 @@ async fn main() {
     vertex: wgpu::VertexState {
       module: &shader,
       entry_point: "vs_main",
-      buffers: &[wgpu::VertexBufferLayout {
-        array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-        step_mode: wgpu::VertexStepMode::Vertex,
-        attributes: &[wgpu::VertexAttribute {
-          offset: 0,
-          shader_location: 0,
-          format: wgpu::VertexFormat::Float32x3,
-        }],
-      }],
+      buffers: &[vertex_buffer_layout.clone(), instance_buffer_layout.clone()],
     },
     fragment: Some(wgpu::FragmentState {
       module: &shader,
       entry_point: "fs_main",
       targets: &[Some(wgpu::ColorTargetState {
         format: surface_format,
-        blend: Some(wgpu::BlendState::REPLACE),
+        blend: Some(premultiplied_alpha_blend),
         write_mask: wgpu::ColorWrites::ALL,
       })],
     }),
@@ -198,7 +309,13 @@ async fn main() {
       unclipped_depth: false,
       conservative: false,
     },
-    depth_stencil: None,
+    depth_stencil: Some(wgpu::DepthStencilState {
+      format: DEPTH_FORMAT,
+      depth_write_enabled: true,
+      depth_compare: wgpu::CompareFunction::LessEqual,
+      stencil: wgpu::StencilState::default(),
+      bias: wgpu::DepthBiasState::default(),
+    }),
     multisample: wgpu::MultisampleState::default(),
     multiview: None,
   });
@@ -206,7 +323,7 @@ async fn main() {
   // Triangle pipeline
   let triangle_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
     label: Some("Triangle Pipeline Layout"),
-    bind_group_layouts: &[&uniform_bind_group_layout, &bind_group_layout],
+    bind_group_layouts: &[&uniform_bind_group_layout],
     push_constant_ranges: &[],
   });
 
@@ -216,22 +333,14 @@ async fn main() {
     vertex: wgpu::VertexState {
       module: &shader,
       entry_point: "vs_main",
-      buffers: &[wgpu::VertexBufferLayout {
-        array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-        step_mode: wgpu::VertexStepMode::Vertex,
-        attributes: &[wgpu::VertexAttribute {
-          offset: 0,
-          shader_location: 0,
-          format: wgpu::VertexFormat::Float32x3,
-        }],
-      }],
+      buffers: &[vertex_buffer_layout.clone(), instance_buffer_layout.clone()],
     },
     fragment: Some(wgpu::FragmentState {
       module: &shader,
       entry_point: "fs_main",
       targets: &[Some(wgpu::ColorTargetState {
         format: surface_format,
-        blend: Some(wgpu::BlendState::REPLACE),
+        blend: Some(premultiplied_alpha_blend),
         write_mask: wgpu::ColorWrites::ALL,
       })],
     }),
@@ -244,7 +353,210 @@ async fn main() {
       unclipped_depth: false,
       conservative: false,
     },
-    depth_stencil: None,
+    depth_stencil: Some(wgpu::DepthStencilState {
+      format: DEPTH_FORMAT,
+      depth_write_enabled: true,
+      depth_compare: wgpu::CompareFunction::LessEqual,
+      stencil: wgpu::StencilState::default(),
+      bias: wgpu::DepthBiasState::default(),
+    }),
+    multisample: wgpu::MultisampleState::default(),
+    multiview: None,
+  });
+
+  // Solid fill pipeline, shared by every solid-filled shape: filled circles,
+  // filled triangles, and tessellated paths are all a TriangleList of plain
+  // `[f32; 3]` positions, so one pipeline covers all three.
+  let solid_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+    label: Some("Solid Pipeline Layout"),
+    bind_group_layouts: &[&uniform_bind_group_layout],
+    push_constant_ranges: &[],
+  });
+
+  let solid_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    label: Some("Solid Pipeline"),
+    layout: Some(&solid_pipeline_layout),
+    vertex: wgpu::VertexState {
+      module: &shader,
+      entry_point: "vs_main",
+      buffers: &[vertex_buffer_layout.clone(), instance_buffer_layout.clone()],
+    },
+    fragment: Some(wgpu::FragmentState {
+      module: &shader,
+      entry_point: "fs_main",
+      targets: &[Some(wgpu::ColorTargetState {
+        format: surface_format,
+        blend: Some(premultiplied_alpha_blend),
+        write_mask: wgpu::ColorWrites::ALL,
+      })],
+    }),
+    primitive: wgpu::PrimitiveState {
+      topology: wgpu::PrimitiveTopology::TriangleList,
+      strip_index_format: None,
+      front_face: wgpu::FrontFace::Ccw,
+      cull_mode: None,
+      polygon_mode: wgpu::PolygonMode::Fill,
+      unclipped_depth: false,
+      conservative: false,
+    },
+    depth_stencil: Some(wgpu::DepthStencilState {
+      format: DEPTH_FORMAT,
+      depth_write_enabled: true,
+      depth_compare: wgpu::CompareFunction::LessEqual,
+      stencil: wgpu::StencilState::default(),
+      bias: wgpu::DepthBiasState::default(),
+    }),
+    multisample: wgpu::MultisampleState::default(),
+    multiview: None,
+  });
+
+  // -------------------------------------
+  // Sprite pipeline: a textured quad, sampled through its own bind group
+  // since each sprite has its own texture.
+  // -------------------------------------
+  let texture_bind_group_layout =
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Texture BGL"),
+      entries: &[
+        wgpu::BindGroupLayoutEntry {
+          binding: 0,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+          },
+          count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+          binding: 1,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+          count: None,
+        },
+      ],
+    });
+
+  let sprite_vertex_buffer_layout = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<[f32; 5]>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Vertex,
+    attributes: &[
+      wgpu::VertexAttribute {
+        offset: 0,
+        shader_location: 0,
+        format: wgpu::VertexFormat::Float32x3,
+      },
+      wgpu::VertexAttribute {
+        offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+        shader_location: 6,
+        format: wgpu::VertexFormat::Float32x2,
+      },
+    ],
+  };
+
+  let sprite_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+    label: Some("Sprite Pipeline Layout"),
+    bind_group_layouts: &[&uniform_bind_group_layout, &texture_bind_group_layout],
+    push_constant_ranges: &[],
+  });
+
+  let sprite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    label: Some("Sprite Pipeline"),
+    layout: Some(&sprite_pipeline_layout),
+    vertex: wgpu::VertexState {
+      module: &shader,
+      entry_point: "vs_sprite",
+      buffers: &[sprite_vertex_buffer_layout, instance_buffer_layout.clone()],
+    },
+    fragment: Some(wgpu::FragmentState {
+      module: &shader,
+      entry_point: "fs_sprite",
+      targets: &[Some(wgpu::ColorTargetState {
+        format: surface_format,
+        blend: Some(premultiplied_alpha_blend),
+        write_mask: wgpu::ColorWrites::ALL,
+      })],
+    }),
+    primitive: wgpu::PrimitiveState {
+      topology: wgpu::PrimitiveTopology::TriangleList,
+      strip_index_format: None,
+      front_face: wgpu::FrontFace::Ccw,
+      cull_mode: None,
+      polygon_mode: wgpu::PolygonMode::Fill,
+      unclipped_depth: false,
+      conservative: false,
+    },
+    depth_stencil: Some(wgpu::DepthStencilState {
+      format: DEPTH_FORMAT,
+      depth_write_enabled: true,
+      depth_compare: wgpu::CompareFunction::LessEqual,
+      stencil: wgpu::StencilState::default(),
+      bias: wgpu::DepthBiasState::default(),
+    }),
+    multisample: wgpu::MultisampleState::default(),
+    multiview: None,
+  });
+
+  // -------------------------------------
+  // Gradient pipeline: fills a tessellated polygon by interpolating between
+  // color stops instead of sampling a flat color, through its own uniform
+  // bound at group 1 (binding 2, alongside but distinct from the sprite
+  // pipeline's texture/sampler bindings at that same group).
+  // -------------------------------------
+  let gradient_bind_group_layout =
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Gradient BGL"),
+      entries: &[wgpu::BindGroupLayoutEntry {
+        binding: 2,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+          ty: wgpu::BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: None,
+        },
+        count: None,
+      }],
+    });
+
+  let gradient_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+    label: Some("Gradient Pipeline Layout"),
+    bind_group_layouts: &[&uniform_bind_group_layout, &gradient_bind_group_layout],
+    push_constant_ranges: &[],
+  });
+
+  let gradient_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    label: Some("Gradient Pipeline"),
+    layout: Some(&gradient_pipeline_layout),
+    vertex: wgpu::VertexState {
+      module: &shader,
+      entry_point: "vs_gradient",
+      buffers: &[vertex_buffer_layout, instance_buffer_layout],
+    },
+    fragment: Some(wgpu::FragmentState {
+      module: &shader,
+      entry_point: "fs_gradient",
+      targets: &[Some(wgpu::ColorTargetState {
+        format: surface_format,
+        blend: Some(premultiplied_alpha_blend),
+        write_mask: wgpu::ColorWrites::ALL,
+      })],
+    }),
+    primitive: wgpu::PrimitiveState {
+      topology: wgpu::PrimitiveTopology::TriangleList,
+      strip_index_format: None,
+      front_face: wgpu::FrontFace::Ccw,
+      cull_mode: None,
+      polygon_mode: wgpu::PolygonMode::Fill,
+      unclipped_depth: false,
+      conservative: false,
+    },
+    depth_stencil: Some(wgpu::DepthStencilState {
+      format: DEPTH_FORMAT,
+      depth_write_enabled: true,
+      depth_compare: wgpu::CompareFunction::LessEqual,
+      stencil: wgpu::StencilState::default(),
+      bias: wgpu::DepthBiasState::default(),
+    }),
     multisample: wgpu::MultisampleState::default(),
     multiview: None,
   });
@@ -252,15 +564,162 @@ async fn main() {
   // -------------------------------------
   // Create some shapes
   // -------------------------------------
-  let mut shapes: Vec<Shape> = vec![
-    Shape::new_circle(&device, &bind_group_layout, glam::vec3(-0.5, 0.0, 0.0), 1.3),
-    Shape::new_triangle(&device, &bind_group_layout, glam::vec3(0.5, 0.5, 0.0), 0.2),
-    Shape::new_triangle(&device, &bind_group_layout, glam::vec3(0.2, 0.2, 0.0), 0.4),
+  let shapes: Vec<Shape> = vec![
+    Shape::new_circle(
+      glam::vec3(-0.5, 0.0, 0.0),
+      1.3,
+      FillMode::Wireframe,
+      [1.0, 1.0, 1.0, 1.0],
+    ),
+    Shape::new_triangle(
+      glam::vec3(0.5, 0.5, 0.0),
+      0.2,
+      FillMode::Wireframe,
+      [1.0, 1.0, 1.0, 1.0],
+    ),
+    Shape::new_triangle(
+      glam::vec3(0.2, 0.2, 0.0),
+      0.4,
+      FillMode::Solid,
+      [0.8, 0.2, 0.2, 0.6],
+    ),
+    // Pushed behind the wireframe circle (z = 0.0 above) on purpose: the
+    // circle's outline overlaps this path around (-0.75, -0.6), and since the
+    // path is drawn after the circle in the render pass, only the depth
+    // buffer stops it from painting over the outline it's actually behind.
+    Shape::new_path(
+      &device,
+      vec![
+        glam::vec2(-0.2, -0.3),
+        glam::vec2(0.2, -0.3),
+        glam::vec2(0.3, 0.0),
+        glam::vec2(0.0, 0.3),
+        glam::vec2(-0.3, 0.0),
+      ],
+      glam::vec3(-0.6, -0.6, 0.3),
+      1.0,
+      [0.2, 0.5, 0.9, 1.0],
+    ),
+    Shape::new_sprite(
+      &device,
+      &queue,
+      &texture_bind_group_layout,
+      include_bytes!("../assets/sprite.png"),
+      glam::vec3(0.6, 0.6, 0.0),
+      0.5,
+    ),
+    Shape::new_gradient(
+      &device,
+      &gradient_bind_group_layout,
+      vec![
+        glam::vec2(-0.3, -0.3),
+        glam::vec2(0.3, -0.3),
+        glam::vec2(0.3, 0.3),
+        glam::vec2(-0.3, 0.3),
+      ],
+      glam::vec3(0.6, -0.6, 0.0),
+      1.0,
+      GradientKind::Linear,
+      &[
+        GradientStop {
+          offset: 0.0,
+          color: [1.0, 0.2, 0.2, 1.0],
+        },
+        GradientStop {
+          offset: 0.5,
+          color: [1.0, 0.9, 0.2, 1.0],
+        },
+        GradientStop {
+          offset: 1.0,
+          color: [0.2, 0.4, 1.0, 1.0],
+        },
+      ],
+      // The square's points span [-0.3, 0.3] before the shape's own
+      // translation, so undo that translation and rescale by 1/0.3 to bring
+      // it into the [-1, 1] gradient space the shader expects.
+      glam::Mat4::from_scale(glam::Vec3::splat(1.0 / 0.3))
+        * glam::Mat4::from_translation(-glam::vec3(0.6, -0.6, 0.0)),
+    ),
   ];
 
+  // Batch instances by (kind, fill mode) so each bucket is a single instanced
+  // draw call. Paths, sprites and gradients aren't batched at all: each has
+  // its own mesh (a tessellation, a texture, or a gradient uniform) that
+  // isn't shared with anything else.
+  let mut instances_by_bucket: HashMap<(ShapeKind, FillMode), Vec<InstanceData>> = HashMap::new();
+  for shape in &shapes {
+    if matches!(
+      shape.shape_type.kind(),
+      ShapeKind::Path | ShapeKind::Sprite | ShapeKind::Gradient
+    ) {
+      continue;
+    }
+    instances_by_bucket
+      .entry((shape.shape_type.kind(), shape.fill_mode))
+      .or_default()
+      .push(shape.instance_data());
+  }
+
+  let circle_wireframe_instances = instances_by_bucket
+    .get(&(ShapeKind::Circle, FillMode::Wireframe))
+    .cloned()
+    .unwrap_or_default();
+  let circle_solid_instances = instances_by_bucket
+    .get(&(ShapeKind::Circle, FillMode::Solid))
+    .cloned()
+    .unwrap_or_default();
+  let triangle_wireframe_instances = instances_by_bucket
+    .get(&(ShapeKind::Triangle, FillMode::Wireframe))
+    .cloned()
+    .unwrap_or_default();
+  let triangle_solid_instances = instances_by_bucket
+    .get(&(ShapeKind::Triangle, FillMode::Solid))
+    .cloned()
+    .unwrap_or_default();
+
+  let circle_wireframe_instance_buffer =
+    build_instance_buffer(&device, "Circle Wireframe Instance Buffer", &circle_wireframe_instances);
+  let circle_solid_instance_buffer =
+    build_instance_buffer(&device, "Circle Solid Instance Buffer", &circle_solid_instances);
+  let triangle_wireframe_instance_buffer = build_instance_buffer(
+    &device,
+    "Triangle Wireframe Instance Buffer",
+    &triangle_wireframe_instances,
+  );
+  let triangle_solid_instance_buffer =
+    build_instance_buffer(&device, "Triangle Solid Instance Buffer", &triangle_solid_instances);
+
+  // Each path shape draws on its own with a single-instance buffer, since its
+  // mesh (and therefore its model matrix) isn't shared with anything else.
+  // The instance slice always has exactly one entry here, so the bucket is
+  // never empty and `build_instance_buffer` always returns `Some`.
+  let path_instance_buffers: Vec<wgpu::Buffer> = shapes
+    .iter()
+    .filter(|shape| shape.path_mesh.is_some())
+    .map(|shape| build_instance_buffer(&device, "Path Instance Buffer", &[shape.instance_data()]).unwrap())
+    .collect();
+
+  // Same reasoning as paths: each sprite gets its own single-instance buffer.
+  let sprite_instance_buffers: Vec<wgpu::Buffer> = shapes
+    .iter()
+    .filter(|shape| shape.texture_bind_group.is_some())
+    .map(|shape| build_instance_buffer(&device, "Sprite Instance Buffer", &[shape.instance_data()]).unwrap())
+    .collect();
+
+  // Same reasoning again: each gradient shape gets its own single-instance
+  // buffer, since its gradient uniform (and mesh) isn't shared either.
+  let gradient_instance_buffers: Vec<wgpu::Buffer> = shapes
+    .iter()
+    .filter(|shape| shape.gradient_mesh.is_some())
+    .map(|shape| build_instance_buffer(&device, "Gradient Instance Buffer", &[shape.instance_data()]).unwrap())
+    .collect();
+
   // -------------------------------------
   // Event loop
   // -------------------------------------
+  let mut is_panning = false;
+  let mut last_cursor_position: Option<winit::dpi::PhysicalPosition<f64>> = None;
+
   event_loop.run(move |event, _, control_flow| {
     *control_flow = ControlFlow::Poll;
 
@@ -282,11 +741,11 @@ async fn main() {
           label: Some("Render Encoder"),
         });
 
-        // Update aspect ratio in the uniform buffer if window size changes
-        let new_aspect_ratio = size.width as f32 / size.height as f32;
-        if (new_aspect_ratio - uniforms.aspect_ratio).abs() > f32::EPSILON {
-          uniforms.aspect_ratio = new_aspect_ratio;
-          queue.write_buffer(&uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+        // Only re-upload the view-projection matrix when pan/zoom/resize
+        // actually touched the camera this frame.
+        if camera_dirty {
+          queue.write_buffer(&uniform_buffer, 0, bytemuck::bytes_of(&camera.to_uniforms()));
+          camera_dirty = false;
         }
 
         // Start render pass
@@ -306,29 +765,89 @@ async fn main() {
                 store: true,
               },
             })],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+              view: &depth_view,
+              depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.0),
+                store: true,
+              }),
+              stencil_ops: None,
+            }),
           });
 
-          // Draw all shapes
-          for shape in &shapes {
-            match shape.shape_type {
-              ShapeType::Circle => {
-                render_pass.set_pipeline(&circle_pipeline);
-                render_pass.set_bind_group(0, &uniform_bind_group, &[]);
-                render_pass.set_bind_group(1, &shape.bind_group, &[]);
-                render_pass.set_vertex_buffer(0, circle_vertex_buffer.slice(..));
-                render_pass
-                  .set_index_buffer(circle_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                render_pass.draw_indexed(0..circle_index_count, 0, 0..1);
-              }
-              ShapeType::Triangle => {
-                render_pass.set_pipeline(&triangle_pipeline);
-                render_pass.set_bind_group(0, &uniform_bind_group, &[]);
-                render_pass.set_bind_group(1, &shape.bind_group, &[]);
-                render_pass.set_vertex_buffer(0, triangle_vertex_buffer.slice(..));
-                render_pass.draw(0..3, 0..1);
-              }
-            }
+          render_pass.set_bind_group(0, &uniform_bind_group, &[]);
+
+          if let Some(instance_buffer) = &circle_wireframe_instance_buffer {
+            render_pass.set_pipeline(&circle_pipeline);
+            render_pass.set_vertex_buffer(0, circle_vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.set_index_buffer(circle_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..circle_index_count, 0, 0..circle_wireframe_instances.len() as u32);
+          }
+
+          if let Some(instance_buffer) = &circle_solid_instance_buffer {
+            render_pass.set_pipeline(&solid_pipeline);
+            render_pass.set_vertex_buffer(0, filled_circle_vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass
+              .set_index_buffer(filled_circle_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..filled_circle_index_count, 0, 0..circle_solid_instances.len() as u32);
+          }
+
+          if let Some(instance_buffer) = &triangle_wireframe_instance_buffer {
+            render_pass.set_pipeline(&triangle_pipeline);
+            render_pass.set_vertex_buffer(0, triangle_vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.draw(0..3, 0..triangle_wireframe_instances.len() as u32);
+          }
+
+          if let Some(instance_buffer) = &triangle_solid_instance_buffer {
+            render_pass.set_pipeline(&solid_pipeline);
+            render_pass.set_vertex_buffer(0, triangle_vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.draw(0..3, 0..triangle_solid_instances.len() as u32);
+          }
+
+          for (shape, instance_buffer) in shapes
+            .iter()
+            .filter(|shape| shape.path_mesh.is_some())
+            .zip(path_instance_buffers.iter())
+          {
+            let mesh = shape.path_mesh.as_ref().unwrap();
+            render_pass.set_pipeline(&solid_pipeline);
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+          }
+
+          for (shape, instance_buffer) in shapes
+            .iter()
+            .filter(|shape| shape.texture_bind_group.is_some())
+            .zip(sprite_instance_buffers.iter())
+          {
+            let texture_bind_group = shape.texture_bind_group.as_ref().unwrap();
+            render_pass.set_pipeline(&sprite_pipeline);
+            render_pass.set_bind_group(1, texture_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, sprite_vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.set_index_buffer(sprite_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..sprite_index_count, 0, 0..1);
+          }
+
+          for (shape, instance_buffer) in shapes
+            .iter()
+            .filter(|shape| shape.gradient_mesh.is_some())
+            .zip(gradient_instance_buffers.iter())
+          {
+            let mesh = shape.gradient_mesh.as_ref().unwrap();
+            let gradient_bind_group = shape.gradient_bind_group.as_ref().unwrap();
+            render_pass.set_pipeline(&gradient_pipeline);
+            render_pass.set_bind_group(1, gradient_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
           }
         }
 
@@ -349,9 +868,48 @@ async fn main() {
         surface_config.width = new_size.width;
         surface_config.height = new_size.height;
         surface.configure(&device, &surface_config);
+        depth_view = create_depth_view(&device, &surface_config);
 
-        uniforms.aspect_ratio = new_size.width as f32 / new_size.height as f32;
-        queue.write_buffer(&uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+        camera.aspect = new_size.width as f32 / new_size.height as f32;
+        camera_dirty = true;
+      }
+      Event::WindowEvent {
+        event: WindowEvent::MouseInput {
+          state,
+          button: MouseButton::Left,
+          ..
+        },
+        ..
+      } => {
+        is_panning = state == ElementState::Pressed;
+      }
+      Event::WindowEvent {
+        event: WindowEvent::CursorMoved { position, .. },
+        ..
+      } => {
+        if is_panning {
+          if let Some(last) = last_cursor_position {
+            let delta = glam::vec2(
+              (position.x - last.x) as f32,
+              (position.y - last.y) as f32,
+            );
+            let viewport_size = glam::vec2(surface_config.width as f32, surface_config.height as f32);
+            camera.pan(delta, viewport_size);
+            camera_dirty = true;
+          }
+        }
+        last_cursor_position = Some(position);
+      }
+      Event::WindowEvent {
+        event: WindowEvent::MouseWheel { delta, .. },
+        ..
+      } => {
+        let scroll = match delta {
+          MouseScrollDelta::LineDelta(_, y) => y,
+          MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 100.0,
+        };
+        camera.zoom_by(scroll);
+        camera_dirty = true;
       }
       Event::MainEventsCleared => {
         // Request a redraw
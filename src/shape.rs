@@ -1,96 +1,391 @@
 use bytemuck::{Pod, Zeroable};
+use lyon::path::Path as LyonPath;
+use lyon::tessellation::{
+  BuffersBuilder, FillOptions, FillTessellator, FillVertex, FillVertexConstructor, VertexBuffers,
+};
 use wgpu::util::DeviceExt;
 
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct InstanceData {
   pub model_matrix: [[f32; 4]; 4],
+  pub color: [f32; 4],
+}
+
+// Which batch a shape's instances are grouped into for a draw call. `ShapeType`
+// itself can't be used as a hash key once `Path` carries per-shape point data.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ShapeKind {
+  Circle,
+  Triangle,
+  Path,
+  Sprite,
+  Gradient,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FillMode {
+  Wireframe,
+  Solid,
 }
 
 pub enum ShapeType {
   Circle,
   Triangle,
+  Path(Vec<glam::Vec2>),
+  Sprite,
+  Gradient(Vec<glam::Vec2>),
+}
+
+impl ShapeType {
+  pub fn kind(&self) -> ShapeKind {
+    match self {
+      ShapeType::Circle => ShapeKind::Circle,
+      ShapeType::Triangle => ShapeKind::Triangle,
+      ShapeType::Path(_) => ShapeKind::Path,
+      ShapeType::Sprite => ShapeKind::Sprite,
+      ShapeType::Gradient(_) => ShapeKind::Gradient,
+    }
+  }
+}
+
+// A tessellated polygon's geometry, built once at construction time since
+// each `Path`/`Gradient` shape has its own unique points and can't share a
+// batched mesh.
+pub struct TessellatedMesh {
+  pub vertex_buffer: wgpu::Buffer,
+  pub index_buffer: wgpu::Buffer,
+  pub index_count: u32,
+}
+
+struct PathVertexCtor;
+
+impl FillVertexConstructor<[f32; 3]> for PathVertexCtor {
+  fn new_vertex(&mut self, vertex: FillVertex) -> [f32; 3] {
+    let position = vertex.position();
+    [position.x, position.y, 0.0]
+  }
+}
+
+// Closes `points` into a loop and fills it with lyon, returning flat
+// vertex/index data ready to upload. Shared by `new_path` and `new_gradient`
+// since both fill an arbitrary closed polygon, just with a different pipeline.
+fn tessellate_polygon(points: &[glam::Vec2]) -> (Vec<[f32; 3]>, Vec<u32>) {
+  let mut builder = LyonPath::builder();
+  if let Some(first) = points.first() {
+    builder.begin(lyon::geom::point(first.x, first.y));
+    for point in &points[1..] {
+      builder.line_to(lyon::geom::point(point.x, point.y));
+    }
+    builder.close();
+  }
+  let path = builder.build();
+
+  let mut geometry: VertexBuffers<[f32; 3], u32> = VertexBuffers::new();
+  let mut tessellator = FillTessellator::new();
+  tessellator
+    .tessellate_path(
+      &path,
+      &FillOptions::default(),
+      &mut BuffersBuilder::new(&mut geometry, PathVertexCtor),
+    )
+    .expect("failed to tessellate polygon");
+
+  (geometry.vertices, geometry.indices)
+}
+
+// Linear or radial shading between a shape's gradient stops.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum GradientKind {
+  Linear,
+  Radial,
+}
+
+// One color stop in a gradient ramp, at `offset` in `[0, 1]`.
+#[derive(Copy, Clone)]
+pub struct GradientStop {
+  pub offset: f32,
+  pub color: [f32; 4],
+}
+
+// Uploaded as-is to the gradient pipeline's uniform buffer. `offsets` is laid
+// out as two `vec4`s on the shader side (`offsets_a`/`offsets_b`) since WGSL's
+// uniform address space requires `array<f32, N>` elements to be 16-byte
+// strided; four packed `f32`s per `vec4` sidesteps that without changing this
+// struct's layout. `transform` maps a world-space fragment position into
+// gradient space, e.g. so the ramp runs along a chosen axis regardless of the
+// shape's own position/scale.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct GradientUniforms {
+  pub colors: [[f32; 4]; 8],
+  pub offsets: [f32; 8],
+  pub count: u32,
+  pub kind: u32,
+  pub _padding: [u32; 2],
+  pub transform: [[f32; 4]; 4],
+}
+
+impl GradientUniforms {
+  pub fn new(kind: GradientKind, stops: &[GradientStop], transform: glam::Mat4) -> Self {
+    let mut colors = [[0.0; 4]; 8];
+    let mut offsets = [0.0; 8];
+    let count = stops.len().min(8);
+    for (i, stop) in stops.iter().take(8).enumerate() {
+      colors[i] = stop.color;
+      offsets[i] = stop.offset;
+    }
+
+    Self {
+      colors,
+      offsets,
+      count: count as u32,
+      kind: kind as u32,
+      _padding: [0; 2],
+      transform: transform.to_cols_array_2d(),
+    }
+  }
 }
 
 pub struct Shape {
   pub shape_type: ShapeType,
   pub position: glam::Vec3,
   pub scale: f32,
-  pub instance_buffer: wgpu::Buffer,
-  pub bind_group: wgpu::BindGroup,
+  pub fill_mode: FillMode,
+  pub color: [f32; 4],
+  pub path_mesh: Option<TessellatedMesh>,
+  pub texture_bind_group: Option<wgpu::BindGroup>,
+  pub gradient_mesh: Option<TessellatedMesh>,
+  pub gradient_bind_group: Option<wgpu::BindGroup>,
 }
 
 impl Shape {
-  pub fn new_circle(
+  pub fn new_circle(position: glam::Vec3, scale: f32, fill_mode: FillMode, color: [f32; 4]) -> Self {
+    Self {
+      shape_type: ShapeType::Circle,
+      position,
+      scale,
+      fill_mode,
+      color,
+      path_mesh: None,
+      texture_bind_group: None,
+      gradient_mesh: None,
+      gradient_bind_group: None,
+    }
+  }
+
+  pub fn new_triangle(position: glam::Vec3, scale: f32, fill_mode: FillMode, color: [f32; 4]) -> Self {
+    Self {
+      shape_type: ShapeType::Triangle,
+      position,
+      scale,
+      fill_mode,
+      color,
+      path_mesh: None,
+      texture_bind_group: None,
+      gradient_mesh: None,
+      gradient_bind_group: None,
+    }
+  }
+
+  // Tessellates an arbitrary closed polygon with lyon and uploads the result
+  // as its own vertex/index buffers. Always solid-filled: lyon gives us a
+  // triangle mesh, not an outline.
+  pub fn new_path(
     device: &wgpu::Device,
-    bind_group_layout: &wgpu::BindGroupLayout,
+    points: Vec<glam::Vec2>,
     position: glam::Vec3,
     scale: f32,
+    color: [f32; 4],
   ) -> Self {
-    // Construct the model matrix for the instance
-    let instance_data = InstanceData {
-      model_matrix: (glam::Mat4::from_translation(position)
-        * glam::Mat4::from_scale(glam::Vec3::splat(scale)))
-      .to_cols_array_2d(),
-    };
+    let (vertices, indices) = tessellate_polygon(&points);
+
+    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Path Vertex Buffer"),
+      contents: bytemuck::cast_slice(&vertices),
+      usage: wgpu::BufferUsages::VERTEX,
+    });
+    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Path Index Buffer"),
+      contents: bytemuck::cast_slice(&indices),
+      usage: wgpu::BufferUsages::INDEX,
+    });
+    let index_count = indices.len() as u32;
+
+    Self {
+      shape_type: ShapeType::Path(points),
+      position,
+      scale,
+      fill_mode: FillMode::Solid,
+      color,
+      path_mesh: Some(TessellatedMesh {
+        vertex_buffer,
+        index_buffer,
+        index_count,
+      }),
+      texture_bind_group: None,
+      gradient_mesh: None,
+      gradient_bind_group: None,
+    }
+  }
+
+  // Tessellates `points` like `new_path`, but fills the result with an
+  // interpolated gradient (via the gradient pipeline) instead of a flat
+  // color. `transform` maps world-space fragment positions into gradient
+  // space, e.g. translated/scaled so the ramp runs cleanly across the shape.
+  #[allow(clippy::too_many_arguments)]
+  pub fn new_gradient(
+    device: &wgpu::Device,
+    gradient_bind_group_layout: &wgpu::BindGroupLayout,
+    points: Vec<glam::Vec2>,
+    position: glam::Vec3,
+    scale: f32,
+    kind: GradientKind,
+    stops: &[GradientStop],
+    transform: glam::Mat4,
+  ) -> Self {
+    let (vertices, indices) = tessellate_polygon(&points);
 
-    let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-      label: Some("Circle Instance Buffer"),
-      contents: bytemuck::cast_slice(&[instance_data]),
-      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Gradient Vertex Buffer"),
+      contents: bytemuck::cast_slice(&vertices),
+      usage: wgpu::BufferUsages::VERTEX,
+    });
+    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Gradient Index Buffer"),
+      contents: bytemuck::cast_slice(&indices),
+      usage: wgpu::BufferUsages::INDEX,
     });
+    let index_count = indices.len() as u32;
 
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-      layout: bind_group_layout,
+    let gradient_uniforms = GradientUniforms::new(kind, stops, transform);
+    let gradient_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Gradient Uniform Buffer"),
+      contents: bytemuck::bytes_of(&gradient_uniforms),
+      usage: wgpu::BufferUsages::UNIFORM,
+    });
+    let gradient_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("Gradient Bind Group"),
+      layout: gradient_bind_group_layout,
       entries: &[wgpu::BindGroupEntry {
-        binding: 0,
-        resource: instance_buffer.as_entire_binding(),
+        binding: 2,
+        resource: gradient_uniform_buffer.as_entire_binding(),
       }],
-      label: Some("Circle Bind Group"),
     });
 
     Self {
-      shape_type: ShapeType::Circle,
+      shape_type: ShapeType::Gradient(points),
       position,
       scale,
-      instance_buffer,
-      bind_group,
+      fill_mode: FillMode::Solid,
+      color: [1.0, 1.0, 1.0, 1.0],
+      path_mesh: None,
+      texture_bind_group: None,
+      gradient_mesh: Some(TessellatedMesh {
+        vertex_buffer,
+        index_buffer,
+        index_count,
+      }),
+      gradient_bind_group: Some(gradient_bind_group),
     }
   }
 
-  pub fn new_triangle(
+  // Decodes `image_bytes` into an RGBA texture and builds the bind group the
+  // sprite pipeline samples it through. Each sprite carries its own texture,
+  // so unlike circles/triangles it can't be batched into a shared buffer.
+  pub fn new_sprite(
     device: &wgpu::Device,
-    bind_group_layout: &wgpu::BindGroupLayout,
+    queue: &wgpu::Queue,
+    texture_bind_group_layout: &wgpu::BindGroupLayout,
+    image_bytes: &[u8],
     position: glam::Vec3,
     scale: f32,
   ) -> Self {
-    let instance_data = InstanceData {
-      model_matrix: (glam::Mat4::from_translation(position)
-        * glam::Mat4::from_scale(glam::Vec3::splat(scale)))
-      .to_cols_array_2d(),
+    let image = image::load_from_memory(image_bytes)
+      .expect("failed to decode sprite image")
+      .to_rgba8();
+    let (width, height) = image.dimensions();
+    let texture_size = wgpu::Extent3d {
+      width,
+      height,
+      depth_or_array_layers: 1,
     };
 
-    let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-      label: Some("Triangle Instance Buffer"),
-      contents: bytemuck::cast_slice(&[instance_data]),
-      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+      label: Some("Sprite Texture"),
+      size: texture_size,
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: wgpu::TextureDimension::D2,
+      format: wgpu::TextureFormat::Rgba8UnormSrgb,
+      usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+      view_formats: &[],
     });
 
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-      layout: bind_group_layout,
-      entries: &[wgpu::BindGroupEntry {
-        binding: 0,
-        resource: instance_buffer.as_entire_binding(),
-      }],
-      label: Some("Triangle Bind Group"),
+    queue.write_texture(
+      wgpu::ImageCopyTexture {
+        texture: &texture,
+        mip_level: 0,
+        origin: wgpu::Origin3d::ZERO,
+        aspect: wgpu::TextureAspect::All,
+      },
+      &image,
+      wgpu::ImageDataLayout {
+        offset: 0,
+        bytes_per_row: Some(4 * width),
+        rows_per_image: Some(height),
+      },
+      texture_size,
+    );
+
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+      label: Some("Sprite Sampler"),
+      address_mode_u: wgpu::AddressMode::ClampToEdge,
+      address_mode_v: wgpu::AddressMode::ClampToEdge,
+      address_mode_w: wgpu::AddressMode::ClampToEdge,
+      mag_filter: wgpu::FilterMode::Linear,
+      min_filter: wgpu::FilterMode::Linear,
+      mipmap_filter: wgpu::FilterMode::Nearest,
+      ..Default::default()
+    });
+
+    let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("Sprite Bind Group"),
+      layout: texture_bind_group_layout,
+      entries: &[
+        wgpu::BindGroupEntry {
+          binding: 0,
+          resource: wgpu::BindingResource::TextureView(&texture_view),
+        },
+        wgpu::BindGroupEntry {
+          binding: 1,
+          resource: wgpu::BindingResource::Sampler(&sampler),
+        },
+      ],
     });
 
     Self {
-      shape_type: ShapeType::Triangle,
+      shape_type: ShapeType::Sprite,
       position,
       scale,
-      instance_buffer,
-      bind_group,
+      fill_mode: FillMode::Solid,
+      color: [1.0, 1.0, 1.0, 1.0],
+      path_mesh: None,
+      texture_bind_group: Some(texture_bind_group),
+      gradient_mesh: None,
+      gradient_bind_group: None,
+    }
+  }
+
+  // The per-instance payload the batched instance buffer expects: model
+  // matrix and tint, no per-shape buffer or bind group anymore.
+  pub fn instance_data(&self) -> InstanceData {
+    InstanceData {
+      model_matrix: (glam::Mat4::from_translation(self.position)
+        * glam::Mat4::from_scale(glam::Vec3::splat(self.scale)))
+      .to_cols_array_2d(),
+      color: self.color,
     }
   }
 }
@@ -3,5 +3,67 @@ use bytemuck::{Pod, Zeroable};
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct Uniforms {
-  pub aspect_ratio: f32,
+  pub view_proj: [[f32; 4]; 4],
+}
+
+// An orthographic 2D camera: `center` is the world point at the viewport
+// center, `zoom` scales the visible extent (larger zoom = closer in).
+pub struct Camera2D {
+  pub center: glam::Vec2,
+  pub zoom: f32,
+  pub aspect: f32,
+}
+
+impl Camera2D {
+  pub fn new(aspect: f32) -> Self {
+    Self {
+      center: glam::Vec2::ZERO,
+      zoom: 1.0,
+      aspect,
+    }
+  }
+
+  fn half_extent(&self) -> glam::Vec2 {
+    let half_height = 1.0 / self.zoom;
+    glam::vec2(half_height * self.aspect, half_height)
+  }
+
+  pub fn view_proj(&self) -> [[f32; 4]; 4] {
+    let half_extent = self.half_extent();
+    let proj = glam::Mat4::orthographic_rh(
+      -half_extent.x,
+      half_extent.x,
+      -half_extent.y,
+      half_extent.y,
+      -1.0,
+      1.0,
+    );
+    let view = glam::Mat4::from_translation(glam::vec3(-self.center.x, -self.center.y, 0.0));
+    (proj * view).to_cols_array_2d()
+  }
+
+  pub fn to_uniforms(&self) -> Uniforms {
+    Uniforms {
+      view_proj: self.view_proj(),
+    }
+  }
+
+  // Translates `center` by a mouse-drag delta given in screen pixels.
+  pub fn pan(&mut self, screen_delta: glam::Vec2, viewport_size: glam::Vec2) {
+    let half_extent = self.half_extent();
+    let world_per_pixel = glam::vec2(
+      2.0 * half_extent.x / viewport_size.x,
+      2.0 * half_extent.y / viewport_size.y,
+    );
+    self.center.x -= screen_delta.x * world_per_pixel.x;
+    // Screen-space y grows downward; world-space y grows upward.
+    self.center.y += screen_delta.y * world_per_pixel.y;
+  }
+
+  // Scales `zoom` by a mouse-wheel delta, clamped so the scene can't invert
+  // or zoom out to nothing.
+  pub fn zoom_by(&mut self, scroll_delta: f32) {
+    let factor = (1.0 + scroll_delta * 0.1).max(0.1);
+    self.zoom = (self.zoom * factor).clamp(0.05, 50.0);
+  }
 }